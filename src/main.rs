@@ -1,38 +1,72 @@
 use dbus::arg::{prop_cast, PropMap};
 use dbus::blocking::stdintf::org_freedesktop_dbus::Properties;
 use dbus::blocking::Connection;
-use serde::Deserialize;
+use dbus::message::{MatchRule, Message};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufReader, Read};
+use std::hash::{Hash, Hasher};
+use std::io::{BufReader, Read, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use regex::Regex;
 
 const CONFIG_FILE_NAME: &str = ".spotify-status";
+const SCROLL_STATE_FILE_NAME: &str = ".spotify-status-scroll-state";
 
 const SPOTIFY_DBUS_DEST: &str = "org.mpris.MediaPlayer2.spotify";
 const MEDIA_INTERFACE_PATH: &str = "/org/mpris/MediaPlayer2";
+const MPRIS_ROOT_INTERFACE: &str = "org.mpris.MediaPlayer2";
 const MPRIS_MEDIA_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+const MPRIS_NAME_PREFIX: &str = "org.mpris.MediaPlayer2.";
+const DBUS_DEST: &str = "org.freedesktop.DBus";
+const DBUS_PATH: &str = "/org/freedesktop/DBus";
 const MEDIA_METADATA_PROP: &str = "Metadata";
+const IDENTITY_PROPERTY: &str = "Identity";
 const TITLE_PROPERTY: &str = "xesam:title";
 const ARTISTS_PROPERTY: &str = "xesam:artist";
+const ALBUM_PROPERTY: &str = "xesam:album";
+const URL_PROPERTY: &str = "xesam:url";
+const LENGTH_PROPERTY: &str = "mpris:length";
+const PLAYBACK_STATUS_PROP: &str = "PlaybackStatus";
 
 const ERR_NO_HOME_DIR: &str = "Error; could not find the home directory of the current user";
 const ERR_UNABLE_TO_OPEN_CONFIG_FILE_BUT_EXISTS: &str =
     "Error; unable to open the config file but it exists";
+const ERR_NO_PLAYER_FOUND: &str = "Error; no MPRIS media player is currently running";
 
 const SPOTIFY_ICON_AWESOME_FONTS: &str = "&#xf1bc;";
+const SPOTIFY_EPISODE_ICON_AWESOME_FONTS: &str = "&#xf2ce;";
 const DEFAULT_COLOR: &str = "white";
 const DEFAULT_MAX_LENGTH: usize = 45;
 const DEFAULT_REMOVE_FEAT : bool = false;
 const DEFAULT_FEAT_REGEX : &str = r"\(feat\. [\w* ]*\)";
+const DEFAULT_OUTPUT: &str = "pango";
+const DEFAULT_DISPLAY_MODE: &str = "trim";
+const SCROLL_SEPARATOR: &str = "   ";
+const DEFAULT_FORMAT: &str = "{icon} {title} (by {artist})";
+const DEFAULT_EPISODE_FORMAT: &str = "{icon} {album}: {title}";
+const DEFAULT_ARTIST_SEPARATOR: &str = ", ";
 
-#[derive(Deserialize)]
+const PLAYBACK_STATUS_PLAYING: &str = "Playing";
+
+#[derive(Deserialize, Clone)]
 struct Config {
     icon: Option<String>,
+    episode_icon: Option<String>,
     color: Option<String>,
     max_length: Option<usize>,
     remove_feat : Option<bool>,
-    feat_regex : Option<String>
+    feat_regex : Option<String>,
+    output: Option<String>,
+    display_mode: Option<String>,
+    format: Option<String>,
+    episode_format: Option<String>,
+    artist_separator: Option<String>,
+    players: Option<Vec<String>>,
+    player_icons: Option<HashMap<String, String>>,
 }
 
 impl Config {
@@ -40,22 +74,126 @@ impl Config {
     fn default() -> Config {
         Config {
             icon: Some(SPOTIFY_ICON_AWESOME_FONTS.to_string()),
+            episode_icon: Some(SPOTIFY_EPISODE_ICON_AWESOME_FONTS.to_string()),
             color: Some(DEFAULT_COLOR.to_string()),
             max_length: Some(DEFAULT_MAX_LENGTH),
             remove_feat: Some(DEFAULT_REMOVE_FEAT),
             feat_regex: Some(DEFAULT_FEAT_REGEX.to_string()),
+            output: Some(DEFAULT_OUTPUT.to_string()),
+            display_mode: Some(DEFAULT_DISPLAY_MODE.to_string()),
+            format: Some(DEFAULT_FORMAT.to_string()),
+            episode_format: Some(DEFAULT_EPISODE_FORMAT.to_string()),
+            players: Some(vec![SPOTIFY_DBUS_DEST.to_string()]),
+            player_icons: None,
+            artist_separator: Some(DEFAULT_ARTIST_SEPARATOR.to_string()),
         }
     }
 }
 
-fn resolve_config() -> std::result::Result<Config, Box<dyn std::error::Error>> {
+/// How a title too long for `max_length` is whittled down to fit.
+#[derive(PartialEq, Debug)]
+enum DisplayMode {
+    /// Collapse to a static `pre...post` ellipsis.
+    Trim,
+    /// Slide a fixed-width window over the text, one character per call.
+    Scroll,
+}
+
+impl DisplayMode {
+    fn from_config(config: &Config) -> DisplayMode {
+        match config.display_mode.as_deref().unwrap_or(DEFAULT_DISPLAY_MODE) {
+            "scroll" => DisplayMode::Scroll,
+            _ => DisplayMode::Trim,
+        }
+    }
+}
+
+/// The persisted scroll position for `DisplayMode::Scroll`, stored next to
+/// the config file so the ticker resumes smoothly across invocations.
+#[derive(Serialize, Deserialize, Default)]
+struct ScrollState {
+    offset: usize,
+    track_key: String,
+}
+
+/// The bar ecosystem we're rendering a status line for.
+#[derive(PartialEq, Debug)]
+enum OutputFormat {
+    /// A single `<span color="…">` string, as consumed by polybar.
+    Pango,
+    /// The icon and text with no markup at all.
+    Plain,
+    /// An i3bar/swaybar JSON protocol block.
+    I3,
+    /// A waybar custom-module JSON block.
+    Waybar,
+}
+
+impl OutputFormat {
+    fn from_config(config: &Config) -> OutputFormat {
+        match config.output.as_deref().unwrap_or(DEFAULT_OUTPUT) {
+            "plain" => OutputFormat::Plain,
+            "i3" => OutputFormat::I3,
+            "waybar" => OutputFormat::Waybar,
+            _ => OutputFormat::Pango,
+        }
+    }
+}
+
+/// Escapes a string for embedding inside a JSON string literal.
+fn escape_json(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+
+    for c in input.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Distinguishes a regular track from a podcast episode so the status line
+/// can format (and icon) each one appropriately.
+#[derive(PartialEq, Debug)]
+enum SpotifyAudioType {
+    Track,
+    Episode,
+}
+
+/// Classifies the currently playing item by inspecting its `xesam:url`, the
+/// same way librespot tells tracks and episodes apart. There's no fallback
+/// to `mpris:trackid`: it's a D-Bus object path, which can't contain the `:`
+/// characters this check looks for, so it could never tell tracks and
+/// episodes apart anyway.
+fn classify_audio_type(metadata: &PropMap) -> SpotifyAudioType {
+    let identifier = prop_cast::<String>(metadata, URL_PROPERTY);
+
+    match identifier {
+        Some(id) if id.contains(":episode:") || id.contains(":show:") => SpotifyAudioType::Episode,
+        _ => SpotifyAudioType::Track,
+    }
+}
+
+/// Resolves a file name to a path in the user's home directory.
+fn home_path(file_name: &str) -> std::result::Result<PathBuf, Box<dyn std::error::Error>> {
     let home_dir = home::home_dir();
 
     if home_dir.is_none() {
         return Err(ERR_NO_HOME_DIR)?;
     }
 
-    let config_path = home_dir.unwrap().join(CONFIG_FILE_NAME);
+    Ok(home_dir.unwrap().join(file_name))
+}
+
+fn resolve_config() -> std::result::Result<Config, Box<dyn std::error::Error>> {
+    let config_path = home_path(CONFIG_FILE_NAME)?;
 
     if !config_path.exists() {
         return Ok(Config::default());
@@ -74,6 +212,84 @@ fn resolve_config() -> std::result::Result<Config, Box<dyn std::error::Error>> {
     Ok(config)
 }
 
+/// Hashes the resolved display text into a short key that changes whenever
+/// the track (or episode) changes, used to reset the scroll offset.
+fn track_key(contents: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
+/// Loads the persisted scroll state, falling back to a fresh state (offset
+/// 0, no track key) if it doesn't exist yet or fails to parse.
+fn load_scroll_state() -> ScrollState {
+    let path = match home_path(SCROLL_STATE_FILE_NAME) {
+        Ok(path) => path,
+        Err(_) => return ScrollState::default(),
+    };
+
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| toml::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Persists the scroll state so the ticker resumes where it left off next
+/// time the tool is invoked.
+fn save_scroll_state(state: &ScrollState) {
+    if let Ok(path) = home_path(SCROLL_STATE_FILE_NAME) {
+        if let Ok(serialized) = toml::to_string(state) {
+            let _ = std::fs::write(path, serialized);
+        }
+    }
+}
+
+/// Slides a fixed-width window of `max_length` chars over `input`, wrapping
+/// around through `SCROLL_SEPARATOR` once the end is reached.
+fn scroll_window(input: &str, max_length: usize, offset: usize) -> String {
+    let chars: Vec<char> = input.chars().collect();
+
+    if chars.len() <= max_length {
+        return input.to_string();
+    }
+
+    let wrapped: Vec<char> = chars
+        .iter()
+        .copied()
+        .chain(SCROLL_SEPARATOR.chars())
+        .collect();
+    let wrap_len = wrapped.len();
+    let start = offset % wrap_len;
+
+    (0..max_length)
+        .map(|i| wrapped[(start + i) % wrap_len])
+        .collect()
+}
+
+/// Applies the configured `DisplayMode`, persisting and advancing the scroll
+/// offset across invocations when in `Scroll` mode.
+fn apply_display_mode(config: &Config, contents: &str, max_length: usize) -> String {
+    match DisplayMode::from_config(config) {
+        DisplayMode::Trim => trim_to_length(contents, max_length),
+        DisplayMode::Scroll => {
+            let key = track_key(contents);
+            let mut state = load_scroll_state();
+
+            if state.track_key != key {
+                state.track_key = key;
+                state.offset = 0;
+            }
+
+            let windowed = scroll_window(contents, max_length, state.offset);
+
+            state.offset = state.offset.wrapping_add(1);
+            save_scroll_state(&state);
+
+            windowed
+        }
+    }
+}
+
 #[test]
 fn test_trim_to_length_short() -> Result<(), String> {
     let less_than_30 = "hello";
@@ -104,6 +320,188 @@ fn test_feat_1() -> Result<(), String> {
     Ok(())
 }
 
+#[test]
+fn test_classify_audio_type_episode() -> Result<(), String> {
+    let mut metadata: PropMap = PropMap::new();
+    metadata.insert(
+        URL_PROPERTY.to_string(),
+        dbus::arg::Variant(Box::new(
+            "spotify:episode:2IMjXybV6PYTHZCKEtGVV9".to_string(),
+        )),
+    );
+    assert_eq!(classify_audio_type(&metadata), SpotifyAudioType::Episode);
+    Ok(())
+}
+
+#[test]
+fn test_classify_audio_type_track() -> Result<(), String> {
+    let mut metadata: PropMap = PropMap::new();
+    metadata.insert(
+        URL_PROPERTY.to_string(),
+        dbus::arg::Variant(Box::new(
+            "spotify:track:2IMjXybV6PYTHZCKEtGVV9".to_string(),
+        )),
+    );
+    assert_eq!(classify_audio_type(&metadata), SpotifyAudioType::Track);
+    Ok(())
+}
+
+#[test]
+fn test_classify_audio_type_no_url_defaults_to_track() -> Result<(), String> {
+    let metadata: PropMap = PropMap::new();
+    assert_eq!(classify_audio_type(&metadata), SpotifyAudioType::Track);
+    Ok(())
+}
+
+#[test]
+fn test_output_format_from_config_waybar() -> Result<(), String> {
+    let mut config = Config::default();
+    config.output = Some("waybar".to_string());
+    assert_eq!(OutputFormat::from_config(&config), OutputFormat::Waybar);
+    Ok(())
+}
+
+#[test]
+fn test_output_format_from_config_default_is_pango() -> Result<(), String> {
+    let config = Config::default();
+    assert_eq!(OutputFormat::from_config(&config), OutputFormat::Pango);
+    Ok(())
+}
+
+#[test]
+fn test_scroll_window_short_is_unchanged() -> Result<(), String> {
+    assert_eq!(scroll_window("hello", 10, 0), "hello");
+    Ok(())
+}
+
+#[test]
+fn test_scroll_window_advances_and_wraps() -> Result<(), String> {
+    let input = "abcde";
+    assert_eq!(scroll_window(input, 3, 0), "abc");
+    assert_eq!(scroll_window(input, 3, 1), "bcd");
+    // Slides into the separator once the end of the text is reached.
+    assert_eq!(scroll_window(input, 3, 4), "e  ");
+    // Wraps all the way back around to the start.
+    assert_eq!(scroll_window(input, 3, 8), "abc");
+    Ok(())
+}
+
+#[test]
+fn test_scroll_window_multibyte_does_not_panic() -> Result<(), String> {
+    let input = "日本語のタイトルです";
+    assert_eq!(scroll_window(input, 3, 0).chars().count(), 3);
+    Ok(())
+}
+
+#[test]
+fn test_render_template_substitutes_all_artists() -> Result<(), String> {
+    let rendered = render_template(
+        "{icon} {all_artists} \u{2014} {title}",
+        "&#xf1bc;",
+        "1x1",
+        "Nova Twins",
+        "Supernova",
+        "Nova Twins, Biig Piig",
+        "Playing",
+        "3:45",
+        false,
+    );
+    assert_eq!(rendered, "&#xf1bc; Nova Twins, Biig Piig \u{2014} 1x1");
+    Ok(())
+}
+
+#[test]
+fn test_render_template_escapes_dynamic_fields_only() -> Result<(), String> {
+    let rendered = render_template(
+        "{icon} {title}",
+        "<icon>",
+        "A & B",
+        "",
+        "",
+        "",
+        "",
+        "",
+        true,
+    );
+    assert_eq!(rendered, "<icon> A &amp; B");
+    Ok(())
+}
+
+#[test]
+fn test_escape_json_escapes_control_characters() -> Result<(), String> {
+    assert_eq!(
+        escape_json("line one\nline two\ttabbed\r"),
+        "line one\\nline two\\ttabbed\\r"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_render_template_does_not_rescan_substituted_values() -> Result<(), String> {
+    let rendered = render_template(
+        "{title} / {album}",
+        "",
+        "{album}",
+        "",
+        "Supernova",
+        "",
+        "",
+        "",
+        false,
+    );
+    assert_eq!(rendered, "{album} / Supernova");
+    Ok(())
+}
+
+#[test]
+fn test_sender_matches_active_player_same_unique_name() -> Result<(), String> {
+    assert!(sender_matches_active_player(Some(":1.42"), Some(":1.42")));
+    Ok(())
+}
+
+#[test]
+fn test_sender_matches_active_player_well_known_name_never_matches() -> Result<(), String> {
+    // A regression guard: the sender on a signal is always a unique name, so
+    // comparing it against a well-known name like "org.mpris.MediaPlayer2.spotify"
+    // must never match, even if that's what the active player's `dest` is.
+    assert!(!sender_matches_active_player(
+        Some(":1.42"),
+        Some("org.mpris.MediaPlayer2.spotify")
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_sender_matches_active_player_unresolved_owner() -> Result<(), String> {
+    assert!(!sender_matches_active_player(Some(":1.42"), None));
+    Ok(())
+}
+
+#[test]
+fn test_pick_preferred_player_honors_order() -> Result<(), String> {
+    let available = vec![
+        "org.mpris.MediaPlayer2.vlc".to_string(),
+        "org.mpris.MediaPlayer2.spotify".to_string(),
+    ];
+    let preference = vec![
+        "org.mpris.MediaPlayer2.spotify".to_string(),
+        "org.mpris.MediaPlayer2.vlc".to_string(),
+    ];
+    assert_eq!(
+        pick_preferred_player(&available, &preference),
+        Some("org.mpris.MediaPlayer2.spotify".to_string())
+    );
+    Ok(())
+}
+
+#[test]
+fn test_pick_preferred_player_none_available() -> Result<(), String> {
+    let available = vec!["org.mpris.MediaPlayer2.vlc".to_string()];
+    let preference = vec!["org.mpris.MediaPlayer2.spotify".to_string()];
+    assert_eq!(pick_preferred_player(&available, &preference), None);
+    Ok(())
+}
+
 fn remove_feat(title : &str, config : &Config) -> String {
     if !config.remove_feat.unwrap_or(false) {
         return title.to_string();
@@ -117,7 +515,8 @@ fn remove_feat(title : &str, config : &Config) -> String {
 }
 
 fn trim_to_length(input: &str, max_length: usize) -> String {
-    let original_str_len = input.len();
+    let chars: Vec<char> = input.chars().collect();
+    let original_str_len = chars.len();
 
     if original_str_len <= max_length {
         return String::from(input);
@@ -126,45 +525,466 @@ fn trim_to_length(input: &str, max_length: usize) -> String {
     let diff = original_str_len - max_length + 3;
     let mid_ish = original_str_len / 2;
 
-    let pre = &input[..mid_ish - diff / 2];
-    let post = &input[mid_ish + diff / 2..];
+    let pre: String = chars[..mid_ish - diff / 2].iter().collect();
+    // `diff` chars are dropped in all, so the result is exactly `max_length`.
+    let post_start = (mid_ish - diff / 2 + diff).min(original_str_len);
+    let post: String = chars[post_start..].iter().collect();
 
     format!("{pre}...{post}")
 }
 
-fn format_for_printing(config: &Config, display_str: &str) -> String {
-    let icon = config.icon.as_deref().unwrap_or(SPOTIFY_ICON_AWESOME_FONTS);
+/// Formats a `mpris:length` value (microseconds) as `m:ss`.
+fn format_duration(length_micros: i64) -> String {
+    let total_seconds = length_micros.max(0) / 1_000_000;
+    let minutes = total_seconds / 60;
+    let seconds = total_seconds % 60;
+    format!("{minutes}:{seconds:02}")
+}
+
+/// Substitutes the `{icon}`, `{title}`, `{artist}`, `{album}`,
+/// `{all_artists}`, `{status}` and `{duration}` placeholders in `template`.
+/// `{icon}` is always inserted as-is (it's trusted markup, like the literal
+/// text around it); the other, dynamic values are HTML-escaped when
+/// `escape_dynamic` is set, i.e. when rendering for Pango.
+#[allow(clippy::too_many_arguments)]
+fn render_template(
+    template: &str,
+    icon: &str,
+    title: &str,
+    artist: &str,
+    album: &str,
+    all_artists: &str,
+    status: &str,
+    duration: &str,
+    escape_dynamic: bool,
+) -> String {
+    let sub = |value: &str| -> String {
+        if escape_dynamic {
+            html_escape::encode_text(value).into_owned()
+        } else {
+            value.to_string()
+        }
+    };
+
+    let fields: &[(&str, String)] = &[
+        ("{icon}", icon.to_string()),
+        ("{title}", sub(title)),
+        ("{artist}", sub(artist)),
+        ("{album}", sub(album)),
+        ("{all_artists}", sub(all_artists)),
+        ("{status}", sub(status)),
+        ("{duration}", sub(duration)),
+    ];
+
+    // A single left-to-right scan, rather than a chain of `str::replace`
+    // calls, so a resolved dynamic value (e.g. a title literally containing
+    // "{album}") is never re-scanned for further placeholders.
+    let mut output = String::with_capacity(template.len());
+    let mut remaining = template;
+    'scan: while !remaining.is_empty() {
+        for (placeholder, value) in fields {
+            if let Some(rest) = remaining.strip_prefix(placeholder) {
+                output.push_str(value);
+                remaining = rest;
+                continue 'scan;
+            }
+        }
+
+        let mut chars = remaining.chars();
+        output.push(chars.next().unwrap());
+        remaining = chars.as_str();
+    }
+
+    output
+}
+
+#[allow(clippy::too_many_arguments)]
+fn format_for_printing(
+    config: &Config,
+    audio_type: &SpotifyAudioType,
+    title: &str,
+    artists: &[String],
+    album: &str,
+    playback_status: &str,
+    duration_micros: Option<i64>,
+    player_identity: &str,
+) -> String {
+    let default_icon = match audio_type {
+        SpotifyAudioType::Episode => config
+            .episode_icon
+            .as_deref()
+            .unwrap_or(SPOTIFY_EPISODE_ICON_AWESOME_FONTS),
+        SpotifyAudioType::Track => config.icon.as_deref().unwrap_or(SPOTIFY_ICON_AWESOME_FONTS),
+    };
+    let icon = config
+        .player_icons
+        .as_ref()
+        .and_then(|icons| icons.get(player_identity))
+        .map(String::as_str)
+        .unwrap_or(default_icon);
     let color = config.color.as_deref().unwrap_or(DEFAULT_COLOR);
     let max_length = config.max_length.unwrap_or(DEFAULT_MAX_LENGTH);
+    let template = match audio_type {
+        SpotifyAudioType::Episode => config.episode_format.as_deref().unwrap_or(DEFAULT_EPISODE_FORMAT),
+        SpotifyAudioType::Track => config.format.as_deref().unwrap_or(DEFAULT_FORMAT),
+    };
+
+    let separator = config
+        .artist_separator
+        .as_deref()
+        .unwrap_or(DEFAULT_ARTIST_SEPARATOR);
+    let artist = artists.first().map(String::as_str).unwrap_or("");
+    let all_artists = artists.join(separator);
+    let duration = duration_micros.map(format_duration).unwrap_or_default();
+
+    let output_format = OutputFormat::from_config(config);
+    let escape_dynamic = output_format == OutputFormat::Pango;
+    // Outside Pango, character references such as the default `&#xf1bc;`
+    // icon are decoded so the output carries the glyph, not the markup.
+    let icon = if escape_dynamic {
+        icon.to_string()
+    } else {
+        html_escape::decode_html_entities(icon).into_owned()
+    };
+
+    let assembled_text = render_template(
+        template,
+        &icon,
+        title,
+        artist,
+        album,
+        &all_artists,
+        playback_status,
+        &duration,
+        escape_dynamic,
+    );
+    let sized_display_str = apply_display_mode(config, &assembled_text, max_length);
+
+    match output_format {
+        OutputFormat::Pango => format!("<span color=\"{color}\">{sized_display_str}</span>"),
+        OutputFormat::Plain => sized_display_str,
+        OutputFormat::I3 => {
+            let full_text = escape_json(&sized_display_str);
+            let color = escape_json(color);
+            format!("{{\"full_text\":\"{full_text}\",\"color\":\"{color}\",\"markup\":\"none\"}}")
+        }
+        OutputFormat::Waybar => {
+            let text = escape_json(&sized_display_str);
+            let tooltip = escape_json(&assembled_text);
+            let class = if playback_status == PLAYBACK_STATUS_PLAYING {
+                "playing"
+            } else {
+                "paused"
+            };
+            format!("{{\"text\":\"{text}\",\"tooltip\":\"{tooltip}\",\"class\":\"{class}\"}}")
+        }
+    }
+}
 
-    // TODO: html escape / encode
-    let sized_display_str = trim_to_length(display_str, max_length);
-    let displayable_text = html_escape::encode_text(&sized_display_str); 
+/// Builds the status line from a single `Metadata` snapshot. Shared by the
+/// one-shot path and the `--watch` daemon so both render identically.
+fn render_status(
+    config: &Config,
+    metadata: &PropMap,
+    playback_status: &str,
+    player_identity: &str,
+) -> String {
+    let audio_type = classify_audio_type(metadata);
+
+    let title_from_spotify = prop_cast::<String>(metadata, TITLE_PROPERTY)
+        .map(String::as_str)
+        .unwrap_or("");
+    let title = remove_feat(title_from_spotify, config);
+
+    let artists: Vec<String> = prop_cast::<Vec<String>>(metadata, ARTISTS_PROPERTY)
+        .cloned()
+        .unwrap_or_default();
+    let album = prop_cast::<String>(metadata, ALBUM_PROPERTY)
+        .map(String::as_str)
+        .unwrap_or("");
+    let duration_micros = prop_cast::<i64>(metadata, LENGTH_PROPERTY).copied();
+
+    format_for_printing(
+        config,
+        &audio_type,
+        &title,
+        &artists,
+        album,
+        playback_status,
+        duration_micros,
+        player_identity,
+    )
+}
 
-    return format!("<span color=\"{color}\">{icon} {displayable_text}</span>");
+/// Prints a freshly rendered status line, newline-terminated and flushed so
+/// that bars tailing our stdout (e.g. polybar's `tail` module) pick it up
+/// immediately.
+fn print_status_line(
+    config: &Config,
+    metadata: &PropMap,
+    playback_status: &str,
+    player_identity: &str,
+) {
+    println!(
+        "{}",
+        render_status(config, metadata, playback_status, player_identity)
+    );
+    let _ = std::io::stdout().flush();
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let conn = Connection::new_session()?;
+/// Fetches the current `Metadata` property from the player at `dest`.
+fn fetch_metadata(conn: &Connection, dest: &str) -> Result<PropMap, dbus::Error> {
+    let player_proxy = conn.with_proxy(dest, MEDIA_INTERFACE_PATH, Duration::from_millis(5000));
 
-    let spotify_dbus_proxy = conn.with_proxy(
-        SPOTIFY_DBUS_DEST,
-        MEDIA_INTERFACE_PATH,
-        Duration::from_millis(5000),
-    );
+    player_proxy.get(MPRIS_MEDIA_INTERFACE, MEDIA_METADATA_PROP)
+}
 
-    let config = resolve_config()?;
+/// Fetches the current `PlaybackStatus` property from the player at `dest`.
+fn fetch_playback_status(conn: &Connection, dest: &str) -> Result<String, dbus::Error> {
+    let player_proxy = conn.with_proxy(dest, MEDIA_INTERFACE_PATH, Duration::from_millis(5000));
 
-    let metadata: PropMap = spotify_dbus_proxy.get(MPRIS_MEDIA_INTERFACE, MEDIA_METADATA_PROP)?;
+    player_proxy.get(MPRIS_MEDIA_INTERFACE, PLAYBACK_STATUS_PROP)
+}
 
-    let title_from_spotify: &String = prop_cast(&metadata, TITLE_PROPERTY).unwrap();
-    let title = remove_feat(title_from_spotify, &config);
+/// Fetches the `Identity` property (e.g. "Spotify", "VLC media player")
+/// from the player at `dest`, used to pick a per-player icon override.
+fn fetch_identity(conn: &Connection, dest: &str) -> Result<String, dbus::Error> {
+    let player_proxy = conn.with_proxy(dest, MEDIA_INTERFACE_PATH, Duration::from_millis(5000));
+
+    player_proxy.get(MPRIS_ROOT_INTERFACE, IDENTITY_PROPERTY)
+}
+
+/// Lists every `org.mpris.MediaPlayer2.*` well-known name currently on the
+/// session bus.
+fn list_mpris_players(conn: &Connection) -> Vec<String> {
+    let dbus_proxy = conn.with_proxy(DBUS_DEST, DBUS_PATH, Duration::from_millis(5000));
+
+    dbus_proxy
+        .method_call::<(Vec<String>,), _, _, _>(DBUS_DEST, "ListNames", ())
+        .map(|(names,)| {
+            names
+                .into_iter()
+                .filter(|name| name.starts_with(MPRIS_NAME_PREFIX))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Resolves a well-known bus name (e.g. `org.mpris.MediaPlayer2.spotify`) to
+/// its current unique owner (e.g. `:1.42`), the form `Message::sender()`
+/// reports on signals.
+fn resolve_unique_owner(conn: &Connection, dest: &str) -> Option<String> {
+    let dbus_proxy = conn.with_proxy(DBUS_DEST, DBUS_PATH, Duration::from_millis(5000));
+
+    dbus_proxy
+        .method_call::<(String,), _, _, _>(DBUS_DEST, "GetNameOwner", (dest,))
+        .map(|(owner,)| owner)
+        .ok()
+}
+
+/// Whether a `PropertiesChanged` signal's sender (a unique name like `:1.42`)
+/// is the currently active player's resolved unique owner. Both sides must
+/// be known and equal; a well-known name never matches a unique name.
+fn sender_matches_active_player(sender: Option<&str>, unique_owner: Option<&str>) -> bool {
+    matches!((sender, unique_owner), (Some(sender), Some(unique_owner)) if sender == unique_owner)
+}
+
+/// Picks the first of `preference` (in order) present in `available`.
+fn pick_preferred_player(available: &[String], preference: &[String]) -> Option<String> {
+    preference
+        .iter()
+        .find(|dest| available.contains(dest))
+        .cloned()
+}
 
-    let artists: &Vec<String> = prop_cast(&metadata, ARTISTS_PROPERTY).unwrap();
+/// Picks which MPRIS player to report on: the first of `config.players` (in
+/// order) that's currently running, falling back to whichever running
+/// player currently reports `PlaybackStatus == "Playing"`, and finally to
+/// any running player at all.
+fn select_player(conn: &Connection, config: &Config) -> Option<String> {
+    let available = list_mpris_players(conn);
 
-    let contents = format!("{title} (by {})", artists[0]);
+    let default_preference = vec![SPOTIFY_DBUS_DEST.to_string()];
+    let preference = config.players.as_ref().unwrap_or(&default_preference);
 
-    print!("{}", format_for_printing(&config, &contents));
+    if let Some(preferred) = pick_preferred_player(&available, preference) {
+        return Some(preferred);
+    }
+
+    let playing = available.iter().find(|dest| {
+        fetch_playback_status(conn, dest)
+            .map(|status| status == PLAYBACK_STATUS_PLAYING)
+            .unwrap_or(false)
+    });
+
+    playing.or_else(|| available.first()).cloned()
+}
+
+/// The MPRIS player the daemon is currently reporting on, shared between the
+/// `PropertiesChanged` and `NameOwnerChanged` handlers so either can observe
+/// (or switch) which player is active.
+struct ActivePlayer {
+    dest: String,
+    /// The unique owner of `dest` (e.g. `:1.42`) at the time it was
+    /// selected, since that's the form `Message::sender()` reports on
+    /// signals, not the well-known name.
+    unique_owner: Option<String>,
+    identity: String,
+    last_playback_status: String,
+}
+
+/// Runs forever, printing a new status line whenever the selected player's
+/// metadata (or playback status) changes, instead of requiring callers to
+/// poll on a timer. Also watches `NameOwnerChanged` so we pick a player up
+/// when one starts after we do, rather than erroring out. If no player is
+/// running yet when the daemon starts, it simply waits for one to appear.
+fn run_daemon(conn: &Connection, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let active: Arc<Mutex<Option<ActivePlayer>>> = Arc::new(Mutex::new(None));
+
+    if let Some(dest) = select_player(conn, config) {
+        let unique_owner = resolve_unique_owner(conn, &dest);
+        let identity = fetch_identity(conn, &dest).unwrap_or_default();
+        let last_playback_status = fetch_playback_status(conn, &dest).unwrap_or_default();
+
+        if let Ok(metadata) = fetch_metadata(conn, &dest) {
+            print_status_line(config, &metadata, &last_playback_status, &identity);
+        }
+
+        *active.lock().unwrap() = Some(ActivePlayer {
+            dest,
+            unique_owner,
+            identity,
+            last_playback_status,
+        });
+    }
+
+    // Not pinned to a sender: the active player can change at runtime (see
+    // the NameOwnerChanged handler below), so senders are filtered in the
+    // callback against whichever player is currently active instead.
+    let mut properties_changed_rule =
+        MatchRule::new_signal("org.freedesktop.DBus.Properties", "PropertiesChanged");
+    properties_changed_rule.path = Some(MEDIA_INTERFACE_PATH.into());
+
+    let properties_changed_config = config.clone();
+    let properties_changed_active = active.clone();
+    conn.add_match(
+        properties_changed_rule,
+        move |(interface_name, changed_properties, _invalidated): (String, PropMap, Vec<String>),
+              conn: &Connection,
+              message: &Message| {
+            if interface_name != MPRIS_MEDIA_INTERFACE {
+                return true;
+            }
+
+            let mut guard = properties_changed_active.lock().unwrap();
+            let active_player = match guard.as_mut() {
+                Some(active_player) => active_player,
+                None => return true,
+            };
+
+            let sender = message.sender();
+            if !sender_matches_active_player(
+                sender.as_deref(),
+                active_player.unique_owner.as_deref(),
+            ) {
+                return true;
+            }
+
+            let playback_status_changed =
+                if let Some(playback_status) =
+                    prop_cast::<String>(&changed_properties, PLAYBACK_STATUS_PROP)
+                {
+                    active_player.last_playback_status = playback_status.clone();
+                    true
+                } else {
+                    false
+                };
+
+            if let Some(metadata) = prop_cast::<PropMap>(&changed_properties, MEDIA_METADATA_PROP)
+            {
+                print_status_line(
+                    &properties_changed_config,
+                    metadata,
+                    &active_player.last_playback_status,
+                    &active_player.identity,
+                );
+            } else if playback_status_changed {
+                // A play/pause toggle only carries PlaybackStatus, not Metadata;
+                // re-fetch it so the line (and the waybar `class`) still refreshes.
+                if let Ok(metadata) = fetch_metadata(conn, &active_player.dest) {
+                    print_status_line(
+                        &properties_changed_config,
+                        &metadata,
+                        &active_player.last_playback_status,
+                        &active_player.identity,
+                    );
+                }
+            }
+
+            true
+        },
+    )?;
+
+    let mut name_owner_changed_rule =
+        MatchRule::new_signal("org.freedesktop.DBus", "NameOwnerChanged");
+    name_owner_changed_rule.sender = Some("org.freedesktop.DBus".into());
+
+    let name_owner_changed_config = config.clone();
+    let name_owner_changed_active = active;
+    conn.add_match(
+        name_owner_changed_rule,
+        move |(name, _old_owner, new_owner): (String, String, String), conn: &Connection, _| {
+            if name.starts_with(MPRIS_NAME_PREFIX) && !new_owner.is_empty() {
+                if let Some(dest) = select_player(conn, &name_owner_changed_config) {
+                    let unique_owner = resolve_unique_owner(conn, &dest);
+                    let identity = fetch_identity(conn, &dest).unwrap_or_default();
+                    let last_playback_status =
+                        fetch_playback_status(conn, &dest).unwrap_or_default();
+
+                    if let Ok(metadata) = fetch_metadata(conn, &dest) {
+                        print_status_line(
+                            &name_owner_changed_config,
+                            &metadata,
+                            &last_playback_status,
+                            &identity,
+                        );
+                    }
+
+                    *name_owner_changed_active.lock().unwrap() = Some(ActivePlayer {
+                        dest,
+                        unique_owner,
+                        identity,
+                        last_playback_status,
+                    });
+                }
+            }
+            true
+        },
+    )?;
+
+    loop {
+        conn.process(Duration::from_millis(1000))?;
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let config = resolve_config()?;
+    let watch = std::env::args().any(|arg| arg == "--watch" || arg == "-w");
+
+    let conn = Connection::new_session()?;
+
+    if watch {
+        return run_daemon(&conn, &config);
+    }
+
+    let player_dest = select_player(&conn, &config).ok_or(ERR_NO_PLAYER_FOUND)?;
+    let player_identity = fetch_identity(&conn, &player_dest).unwrap_or_default();
+    let metadata = fetch_metadata(&conn, &player_dest)?;
+    let playback_status = fetch_playback_status(&conn, &player_dest).unwrap_or_default();
+    print!(
+        "{}",
+        render_status(&config, &metadata, &playback_status, &player_identity)
+    );
 
     Ok(())
 }